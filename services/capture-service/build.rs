@@ -1,4 +1,6 @@
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    tonic_build::compile_protos("../../proto/velvet_capture.proto")?;
+    tonic_build::configure()
+        .bytes(&["."])
+        .compile(&["../../proto/velvet_capture.proto"], &["../../proto"])?;
     Ok(())
-}
\ No newline at end of file
+}
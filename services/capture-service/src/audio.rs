@@ -0,0 +1,279 @@
+use crate::config::AudioSettings;
+use crate::velvet_capture::AudioChunk;
+use bytes::Bytes;
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc as std_mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::{broadcast, Notify};
+
+const BROADCAST_CAPACITY: usize = 32;
+
+/// How often the capture thread wakes to check for a shutdown request while
+/// otherwise blocked waiting for the next batch of samples.
+const SHUTDOWN_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Latest rolling-window ASR result, shared with `stream_brain_context` so
+/// it no longer has to hardcode an empty transcript and zero confidence.
+#[derive(Clone, Default)]
+pub struct Transcript {
+    pub text: String,
+    pub confidence: f32,
+}
+
+/// Captures microphone audio on a dedicated thread (cpal streams aren't
+/// `Send` across an async runtime), chunks the PCM into `AudioChunk`
+/// messages broadcast to every `stream_audio` subscriber, and separately
+/// feeds rolling windows of the same audio to the preproc worker's ASR
+/// endpoint to keep a running transcript for `stream_brain_context`.
+#[derive(Clone)]
+pub struct AudioHub {
+    tx: broadcast::Sender<AudioChunk>,
+    transcript: Arc<Mutex<Transcript>>,
+    /// Checked by the blocking capture thread between sample batches.
+    shutdown_flag: Arc<AtomicBool>,
+    /// Awaited by the async ASR loop, which can't poll an `AtomicBool`.
+    shutdown_notify: Arc<Notify>,
+}
+
+impl AudioHub {
+    /// Builds a hub with no microphone capture attached. Used by session
+    /// replay, which publishes recorded chunks directly instead of
+    /// capturing live audio.
+    pub fn new() -> Self {
+        let (tx, _rx) = broadcast::channel(BROADCAST_CAPACITY);
+        AudioHub {
+            tx,
+            transcript: Arc::new(Mutex::new(Transcript::default())),
+            shutdown_flag: Arc::new(AtomicBool::new(false)),
+            shutdown_notify: Arc::new(Notify::new()),
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<AudioChunk> {
+        self.tx.subscribe()
+    }
+
+    pub fn latest_transcript(&self) -> Transcript {
+        self.transcript.lock().unwrap().clone()
+    }
+
+    /// Publishes a chunk to every current subscriber. Used by session
+    /// replay to re-emit recorded audio in place of a live capture thread.
+    pub fn publish(&self, chunk: AudioChunk) {
+        let _ = self.tx.send(chunk);
+    }
+
+    /// Signals the capture thread and ASR loop to flush their buffered audio
+    /// and stop. Call this during graceful shutdown, before the process
+    /// exits, so the tail of the capture isn't silently dropped.
+    pub fn shutdown(&self) {
+        self.shutdown_flag.store(true, Ordering::SeqCst);
+        self.shutdown_notify.notify_one();
+    }
+
+    pub fn spawn(settings: AudioSettings, asr_endpoint: String) -> Self {
+        let hub = AudioHub::new();
+
+        let capture_tx = hub.tx.clone();
+        let shutdown_flag = hub.shutdown_flag.clone();
+        std::thread::spawn(move || {
+            if let Err(e) = run_capture_thread(settings, capture_tx, shutdown_flag) {
+                eprintln!("❌ Audio capture failed: {}", e);
+            }
+        });
+
+        tokio::spawn(run_asr_loop(
+            hub.subscribe(),
+            hub.transcript.clone(),
+            settings,
+            asr_endpoint,
+            hub.shutdown_notify.clone(),
+        ));
+
+        hub
+    }
+}
+
+impl Default for AudioHub {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn run_capture_thread(
+    settings: AudioSettings,
+    tx: broadcast::Sender<AudioChunk>,
+    shutdown: Arc<AtomicBool>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let host = cpal::default_host();
+    let device = host
+        .default_input_device()
+        .ok_or("no audio input device available")?;
+    let supported = device.default_input_config()?;
+    let sample_format = supported.sample_format();
+    let sample_rate = supported.sample_rate().0;
+    let channels = supported.channels() as u32;
+    let stream_config: cpal::StreamConfig = supported.into();
+
+    let chunk_samples = (sample_rate as u64 * channels as u64 * settings.chunk_ms as u64 / 1000) as usize;
+    let (sample_tx, sample_rx) = std_mpsc::channel::<Vec<i16>>();
+    let err_fn = |err| eprintln!("❌ Audio input stream error: {}", err);
+
+    let stream = match sample_format {
+        cpal::SampleFormat::I16 => device.build_input_stream(
+            &stream_config,
+            move |data: &[i16], _| {
+                let _ = sample_tx.send(data.to_vec());
+            },
+            err_fn,
+            None,
+        )?,
+        cpal::SampleFormat::F32 => device.build_input_stream(
+            &stream_config,
+            move |data: &[f32], _| {
+                let samples = data.iter().map(|s| (s.clamp(-1.0, 1.0) * i16::MAX as f32) as i16).collect();
+                let _ = sample_tx.send(samples);
+            },
+            err_fn,
+            None,
+        )?,
+        other => return Err(format!("unsupported input sample format: {other:?}").into()),
+    };
+
+    stream.play()?;
+    println!("🎤 Starting audio capture stream...");
+
+    let mut buffer: Vec<i16> = Vec::with_capacity(chunk_samples);
+    loop {
+        match sample_rx.recv_timeout(SHUTDOWN_POLL_INTERVAL) {
+            Ok(samples) => {
+                buffer.extend_from_slice(&samples);
+
+                while buffer.len() >= chunk_samples {
+                    let chunk: Vec<i16> = buffer.drain(..chunk_samples).collect();
+                    send_audio_chunk(&tx, chunk, sample_rate, channels);
+                }
+            }
+            Err(std_mpsc::RecvTimeoutError::Timeout) => {}
+            Err(std_mpsc::RecvTimeoutError::Disconnected) => break,
+        }
+
+        if shutdown.load(Ordering::SeqCst) {
+            break;
+        }
+    }
+
+    // Stop the input callback (and drop its sample_tx clone) before
+    // flushing so nothing more can land in `buffer` underneath us.
+    drop(stream);
+
+    // Flush whatever's left in the buffer so the tail of the recording
+    // isn't silently dropped on shutdown.
+    if !buffer.is_empty() {
+        send_audio_chunk(&tx, buffer, sample_rate, channels);
+    }
+
+    Ok(())
+}
+
+fn send_audio_chunk(tx: &broadcast::Sender<AudioChunk>, samples: Vec<i16>, sample_rate: u32, channels: u32) {
+    let data: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as i64;
+
+    let audio_chunk = AudioChunk {
+        data: Bytes::from(data),
+        timestamp,
+        sample_rate: sample_rate as i32,
+        channels: channels as i32,
+    };
+
+    // Ignore send errors: no subscribers currently connected.
+    let _ = tx.send(audio_chunk);
+}
+
+/// Accumulates broadcast `AudioChunk`s into rolling windows and sends each
+/// window to the preproc worker's ASR endpoint, updating `transcript` with
+/// the latest result.
+async fn run_asr_loop(
+    mut rx: broadcast::Receiver<AudioChunk>,
+    transcript: Arc<Mutex<Transcript>>,
+    settings: AudioSettings,
+    asr_endpoint: String,
+    shutdown: Arc<Notify>,
+) {
+    let mut window = Vec::new();
+    let mut window_meta: Option<(i32, i32)> = None; // (sample_rate, channels)
+
+    loop {
+        let chunk = tokio::select! {
+            result = rx.recv() => match result {
+                Ok(chunk) => chunk,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    eprintln!("⚠️ ASR window subscriber lagged, dropped {} chunks", skipped);
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            _ = shutdown.notified() => break,
+        };
+
+        let (sample_rate, channels) = *window_meta.get_or_insert((chunk.sample_rate, chunk.channels));
+        window.extend_from_slice(&chunk.data);
+
+        let window_bytes = (sample_rate as u64 * channels as u64 * 2 * settings.window_ms as u64 / 1000) as usize;
+        if window.len() < window_bytes {
+            continue;
+        }
+
+        send_asr_window(&mut window, &transcript, &asr_endpoint, sample_rate, channels).await;
+    }
+
+    // Flush whatever's left in the window so the tail of the stream still
+    // makes it into the transcript instead of being dropped on shutdown.
+    if let (false, Some((sample_rate, channels))) = (window.is_empty(), window_meta) {
+        send_asr_window(&mut window, &transcript, &asr_endpoint, sample_rate, channels).await;
+    }
+}
+
+async fn send_asr_window(
+    window: &mut Vec<u8>,
+    transcript: &Arc<Mutex<Transcript>>,
+    asr_endpoint: &str,
+    sample_rate: i32,
+    channels: i32,
+) {
+    match send_to_asr_worker(Bytes::from(std::mem::take(window)), asr_endpoint, sample_rate, channels).await {
+        Ok((text, confidence)) => {
+            let mut guard = transcript.lock().unwrap();
+            guard.text = text;
+            guard.confidence = confidence;
+        }
+        Err(e) => eprintln!("❌ ASR request failed: {}", e),
+    }
+}
+
+async fn send_to_asr_worker(
+    pcm_bytes: Bytes,
+    endpoint: &str,
+    sample_rate: i32,
+    channels: i32,
+) -> Result<(String, f32), Box<dyn std::error::Error + Send + Sync + 'static>> {
+    let client = reqwest::Client::new();
+
+    let form = reqwest::multipart::Form::new()
+        .text("sample_rate", sample_rate.to_string())
+        .text("channels", channels.to_string())
+        .part("audio", reqwest::multipart::Part::bytes(pcm_bytes.to_vec())
+            .file_name("window.pcm")
+            .mime_str("audio/L16")?);
+
+    let resp = client.post(endpoint).multipart(form).send().await?;
+    let json: serde_json::Value = resp.json().await?;
+
+    let transcript = json.get("transcript").and_then(|v| v.as_str()).unwrap_or("").to_string();
+    let confidence = json.get("asrConfidence").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
+
+    Ok((transcript, confidence))
+}
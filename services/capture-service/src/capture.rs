@@ -0,0 +1,91 @@
+use crate::config::{CaptureFormat, CaptureSettings};
+use bytes::Bytes;
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::PngEncoder;
+use image::{imageops::FilterType, DynamicImage, ImageEncoder};
+use std::io;
+use xcap::Monitor;
+
+/// A single encoded frame plus the dimensions it was actually captured at,
+/// so callers no longer have to stamp a hardcoded resolution onto it.
+pub struct CapturedFrame {
+    pub data: Bytes,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Captures a frame of the configured display, applying the configured
+/// region crop and downscale, and encodes it to the configured format —
+/// entirely in memory. This replaces the previous implementation, which
+/// shelled out to the `screencapture` CLI and round-tripped the frame
+/// through a fixed path in `/tmp` on every call — a race when more than one
+/// stream captured at once, and needless disk I/O at streaming frame rates.
+pub async fn capture_screen_frame(settings: CaptureSettings) -> io::Result<CapturedFrame> {
+    tokio::task::spawn_blocking(move || capture_screen_frame_blocking(&settings))
+        .await
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?
+}
+
+fn capture_screen_frame_blocking(settings: &CaptureSettings) -> io::Result<CapturedFrame> {
+    let monitor = select_monitor(settings.display)?;
+    let image = monitor
+        .capture_image()
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("screen capture failed: {e}")))?;
+
+    let mut image = DynamicImage::ImageRgba8(image);
+
+    if let Some(region) = settings.region {
+        image = image.crop_imm(region.x, region.y, region.width, region.height);
+    }
+
+    if settings.downscale > 0.0 && (settings.downscale - 1.0).abs() > f32::EPSILON {
+        let width = ((image.width() as f32) * settings.downscale).round().max(1.0) as u32;
+        let height = ((image.height() as f32) * settings.downscale).round().max(1.0) as u32;
+        image = image.resize(width, height, FilterType::Triangle);
+    }
+
+    let data = match settings.format {
+        CaptureFormat::Png => encode_png(&image)?,
+        CaptureFormat::Jpeg => encode_jpeg(&image, settings.jpeg_quality)?,
+    };
+
+    Ok(CapturedFrame {
+        width: image.width(),
+        height: image.height(),
+        data,
+    })
+}
+
+fn select_monitor(index: usize) -> io::Result<Monitor> {
+    let mut monitors = Monitor::all()
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("no displays available: {e}")))?;
+
+    if index < monitors.len() {
+        return Ok(monitors.remove(index));
+    }
+
+    monitors
+        .into_iter()
+        .find(|m| m.is_primary())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "no displays available"))
+}
+
+fn encode_png(image: &DynamicImage) -> io::Result<Bytes> {
+    let rgba = image.to_rgba8();
+    let mut buf = Vec::new();
+    PngEncoder::new(&mut buf)
+        .write_image(&rgba, rgba.width(), rgba.height(), image::ColorType::Rgba8)
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("png encode failed: {e}")))?;
+
+    Ok(Bytes::from(buf))
+}
+
+fn encode_jpeg(image: &DynamicImage, quality: u8) -> io::Result<Bytes> {
+    let rgb = image.to_rgb8();
+    let mut buf = Vec::new();
+    JpegEncoder::new_with_quality(&mut buf, quality)
+        .write_image(&rgb, rgb.width(), rgb.height(), image::ColorType::Rgb8)
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("jpeg encode failed: {e}")))?;
+
+    Ok(Bytes::from(buf))
+}
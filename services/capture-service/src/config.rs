@@ -0,0 +1,227 @@
+use serde::Deserialize;
+use std::path::Path;
+
+/// Default location of the capture pipeline config, relative to the
+/// service's working directory.
+const DEFAULT_CONFIG_PATH: &str = "defaults.toml";
+
+/// Drives every tunable of the capture pipeline that used to be hardcoded
+/// inline: per-stream frame rate, output format/quality, which display (and
+/// region of it) to capture, downscaling, and the preprocessing worker URL.
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(default)]
+pub struct CaptureConfig {
+    pub capture: CaptureSettings,
+    pub streams: StreamSettings,
+    pub audio: AudioSettings,
+    pub preproc: PreprocSettings,
+    pub persistence: PersistenceSettings,
+}
+
+impl CaptureConfig {
+    /// Loads `defaults.toml` from the working directory, falling back to
+    /// built-in defaults if it's missing or fails to parse.
+    pub fn load() -> Self {
+        Self::load_from(Path::new(DEFAULT_CONFIG_PATH))
+    }
+
+    pub fn load_from(path: &Path) -> Self {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => toml::from_str(&contents).unwrap_or_else(|e| {
+                eprintln!("⚠️ Failed to parse {}: {} — using default capture config", path.display(), e);
+                Self::default()
+            }),
+            Err(_) => {
+                println!("ℹ️ No {} found, using default capture config", path.display());
+                Self::default()
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct CaptureSettings {
+    pub format: CaptureFormat,
+    pub jpeg_quality: u8,
+    /// Index into `Monitor::all()`; out-of-range falls back to the primary display.
+    pub display: usize,
+    /// 1.0 = no downscaling, 0.5 = half resolution, etc.
+    pub downscale: f32,
+    pub region: Option<CaptureRegion>,
+}
+
+impl Default for CaptureSettings {
+    fn default() -> Self {
+        Self {
+            format: CaptureFormat::Png,
+            jpeg_quality: 85,
+            display: 0,
+            downscale: 1.0,
+            region: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CaptureFormat {
+    Png,
+    Jpeg,
+}
+
+impl CaptureFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            CaptureFormat::Png => "png",
+            CaptureFormat::Jpeg => "jpg",
+        }
+    }
+
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            CaptureFormat::Png => "image/png",
+            CaptureFormat::Jpeg => "image/jpeg",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct CaptureRegion {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct StreamSettings {
+    pub screen: StreamRate,
+    pub brain_context: StreamRate,
+}
+
+impl Default for StreamSettings {
+    fn default() -> Self {
+        Self {
+            screen: StreamRate { fps: 10.0 },
+            brain_context: StreamRate { fps: 1.0 },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct StreamRate {
+    pub fps: f32,
+}
+
+impl StreamRate {
+    pub fn interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs_f32(1.0 / self.fps.max(0.001))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct AudioSettings {
+    /// Duration of each `AudioChunk` published on the audio stream.
+    pub chunk_ms: u32,
+    /// Size of the rolling PCM window fed to the ASR endpoint.
+    pub window_ms: u32,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            chunk_ms: 200,
+            window_ms: 4000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct PreprocSettings {
+    pub endpoint: String,
+    pub asr_endpoint: String,
+    pub embedding_endpoint: String,
+}
+
+impl Default for PreprocSettings {
+    fn default() -> Self {
+        Self {
+            endpoint: "http://127.0.0.1:8001/velvet/analyze/".to_string(),
+            asr_endpoint: "http://127.0.0.1:8001/velvet/asr/".to_string(),
+            embedding_endpoint: "http://127.0.0.1:8001/velvet/embed/".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct PersistenceSettings {
+    pub backend: PersistenceBackend,
+    pub local: LocalStoreSettings,
+    pub s3: S3StoreSettings,
+}
+
+impl Default for PersistenceSettings {
+    fn default() -> Self {
+        Self {
+            backend: PersistenceBackend::Local,
+            local: LocalStoreSettings::default(),
+            s3: S3StoreSettings::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PersistenceBackend {
+    Local,
+    S3,
+}
+
+impl Default for PersistenceBackend {
+    fn default() -> Self {
+        PersistenceBackend::Local
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct LocalStoreSettings {
+    pub dir: String,
+}
+
+impl Default for LocalStoreSettings {
+    fn default() -> Self {
+        Self {
+            dir: "context-store".to_string(),
+        }
+    }
+}
+
+/// Bucket/endpoint/credentials for an S3-compatible object store (AWS S3,
+/// MinIO, R2, etc). `endpoint` may be left empty to use AWS's default.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct S3StoreSettings {
+    pub bucket: String,
+    pub endpoint: String,
+    pub region: String,
+    pub access_key: String,
+    pub secret_key: String,
+}
+
+impl Default for S3StoreSettings {
+    fn default() -> Self {
+        Self {
+            bucket: String::new(),
+            endpoint: String::new(),
+            region: "us-east-1".to_string(),
+            access_key: String::new(),
+            secret_key: String::new(),
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use crate::capture::capture_screen_frame;
+use crate::config::CaptureConfig;
+use crate::velvet_capture::ScreenFrame;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::broadcast;
+
+const BROADCAST_CAPACITY: usize = 8;
+
+/// Fans a single screen-capture loop out to every subscriber.
+///
+/// Previously each RPC (`stream_screen`, `stream_brain_context`) spawned its
+/// own capture loop, so two connected clients ran two captures against the
+/// same display. `CaptureHub` captures once per tick — at the rate and with
+/// the format/region/downscale from `CaptureConfig` — and publishes the
+/// frame over a broadcast channel; subscribers just forward (or sample)
+/// from their own receiver.
+#[derive(Clone)]
+pub struct CaptureHub {
+    tx: broadcast::Sender<ScreenFrame>,
+}
+
+impl CaptureHub {
+    /// Builds a hub with no capture loop attached. Used by session replay,
+    /// which publishes recorded frames directly instead of capturing live.
+    pub fn new() -> Self {
+        let (tx, _rx) = broadcast::channel(BROADCAST_CAPACITY);
+        CaptureHub { tx }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ScreenFrame> {
+        self.tx.subscribe()
+    }
+
+    /// Publishes a frame to every current subscriber. Used by session
+    /// replay to re-emit recorded frames in place of a live capture loop.
+    pub fn publish(&self, frame: ScreenFrame) {
+        let _ = self.tx.send(frame);
+    }
+
+    /// Spawns the capture loop and returns a handle for subscribing to it.
+    pub fn spawn(config: CaptureConfig) -> Self {
+        let hub = CaptureHub::new();
+
+        let loop_tx = hub.tx.clone();
+        let tick = config.streams.screen.interval();
+        tokio::spawn(async move {
+            println!("🎥 Starting shared screen capture loop...");
+
+            loop {
+                match capture_screen_frame(config.capture.clone()).await {
+                    Ok(frame) => {
+                        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as i64;
+                        let frame_msg = ScreenFrame {
+                            data: frame.data,
+                            timestamp,
+                            width: frame.width as i32,
+                            height: frame.height as i32,
+                        };
+
+                        // Ignore send errors: no subscribers currently connected.
+                        let _ = loop_tx.send(frame_msg);
+                    }
+                    Err(e) => {
+                        eprintln!("❌ Screen capture failed: {}", e);
+                    }
+                }
+
+                tokio::time::sleep(tick).await;
+            }
+        });
+
+        hub
+    }
+}
+
+impl Default for CaptureHub {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,16 +1,32 @@
 use tonic::{transport::Server, Request, Response, Status};
-use velvet_capture::{BrainContextRequest, BrainContextUpdate, ScreenRequest, ScreenFrame, AudioRequest, AudioChunk};
+use velvet_capture::{
+    BrainContextRequest, BrainContextUpdate, ContextHistoryRequest, ContextHistoryResponse, ContextSearchRequest,
+    ContextSearchResponse, ScreenRequest, ScreenFrame, AudioRequest, AudioChunk,
+};
 use futures_core::Stream;
 use tokio_stream::wrappers::ReceiverStream;
 use tokio::sync::mpsc;
+use std::io;
 use std::pin::Pin;
+use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 use reqwest;
 use serde_json;
-use std::process::Command;
-use std::fs;
-use std::io;
-use std::sync::{Arc, Mutex};
+
+mod audio;
+mod capture;
+mod config;
+mod hub;
+mod recording;
+mod search;
+mod store;
+
+use audio::AudioHub;
+use config::{CaptureConfig, PersistenceBackend};
+use hub::CaptureHub;
+use search::VectorStore;
+use store::{ContextStore, LocalContextStore, S3ContextStore, StoredContextUpdate};
+use tokio::sync::broadcast;
 
 pub mod velvet_capture {
     tonic::include_proto!("velvet_capture");
@@ -22,7 +38,17 @@ type ScreenStream = Pin<Box<dyn Stream<Item = Result<ScreenFrame, Status>> + Sen
 type AudioStream = Pin<Box<dyn Stream<Item = Result<AudioChunk, Status>> + Send>>;
 type BrainContextStream = Pin<Box<dyn Stream<Item = Result<BrainContextUpdate, Status>> + Send>>;
 
-struct VelvetCaptureServiceImpl;
+struct VelvetCaptureServiceImpl {
+    hub: CaptureHub,
+    audio_hub: AudioHub,
+    vector_store: VectorStore,
+    context_store: Arc<dyn ContextStore>,
+    /// Broadcasts every `BrainContextUpdate` produced by `stream_brain_context`,
+    /// independent of whether any RPC subscriber is currently attached, so
+    /// session recording can capture it alongside screen/audio.
+    context_tx: broadcast::Sender<BrainContextUpdate>,
+    config: CaptureConfig,
+}
 
 #[tonic::async_trait]
 impl VelvetCaptureService for VelvetCaptureServiceImpl {
@@ -36,35 +62,26 @@ impl VelvetCaptureService for VelvetCaptureServiceImpl {
         _request: Request<ScreenRequest>,
     ) -> Result<Response<Self::StreamScreenStream>, Status> {
         let (tx, rx) = mpsc::channel(4);
-        
+        let mut capture_rx = self.hub.subscribe();
+
         tokio::spawn(async move {
-            println!("🎥 Starting screen capture stream...");
-            
+            println!("🎥 New screen capture subscriber attached...");
+
             loop {
-                match capture_screen_to_png().await {
-                    Ok(png_bytes) => {
-                        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as i64;
-                        let frame_msg = ScreenFrame {
-                            data: png_bytes,
-                            timestamp,
-                            width: 1920, // Default, could be dynamic
-                            height: 1080,
-                        };
-                        
-                        if tx.send(Ok(frame_msg)).await.is_err() {
+                match capture_rx.recv().await {
+                    Ok(frame) => {
+                        if tx.send(Ok(frame)).await.is_err() {
                             break;
                         }
                     }
-                    Err(e) => {
-                        eprintln!("❌ Screen capture failed: {}", e);
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        eprintln!("⚠️ Screen stream subscriber lagged, dropped {} frames", skipped);
                     }
+                    Err(broadcast::error::RecvError::Closed) => break,
                 }
-                
-                // 10 FPS
-                tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
             }
         });
-        
+
         Ok(Response::new(Box::pin(ReceiverStream::new(rx)) as Self::StreamScreenStream))
     }
 
@@ -73,10 +90,24 @@ impl VelvetCaptureService for VelvetCaptureServiceImpl {
         _request: Request<AudioRequest>,
     ) -> Result<Response<Self::StreamAudioStream>, Status> {
         let (tx, rx) = mpsc::channel(8);
-        
+        let mut audio_rx = self.audio_hub.subscribe();
+
         tokio::spawn(async move {
-            println!("🎤 Audio streaming not yet implemented");
-            // TODO: Implement audio capture
+            println!("🎤 New audio subscriber attached...");
+
+            loop {
+                match audio_rx.recv().await {
+                    Ok(chunk) => {
+                        if tx.send(Ok(chunk)).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        eprintln!("⚠️ Audio stream subscriber lagged, dropped {} chunks", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
         });
 
         Ok(Response::new(Box::pin(ReceiverStream::new(rx)) as Self::StreamAudioStream))
@@ -88,54 +119,96 @@ impl VelvetCaptureService for VelvetCaptureServiceImpl {
     ) -> Result<Response<Self::StreamBrainContextStream>, Status> {
         let req = request.into_inner();
         let confidence_threshold = req.confidence_threshold;
-        
+
         let (tx, rx) = mpsc::channel(4);
-        
+        let mut capture_rx = self.hub.subscribe();
+        let interval_ms = self.config.streams.brain_context.interval().as_millis() as i64;
+        let preproc_endpoint = self.config.preproc.endpoint.clone();
+        let capture_format = self.config.capture.format;
+        let audio_hub = self.audio_hub.clone();
+        let vector_store = self.vector_store.clone();
+        let embedding_endpoint = self.config.preproc.embedding_endpoint.clone();
+        let context_store = self.context_store.clone();
+        let context_tx = self.context_tx.clone();
+
         tokio::spawn(async move {
             println!("🧠 Starting unified brain context streaming...");
-            
+            let mut last_processed_at: Option<i64> = None;
+
             loop {
-                match capture_screen_to_png().await {
-                    Ok(png_bytes) => {
-                        // Send to preprocessing worker
-                        match send_to_preproc_worker(png_bytes).await {
-                            Ok((screen_text, _transcript, ocr_confidence)) => {
-                                println!("🔍 RUST DEBUG: OCR Result - Text: '{}', Confidence: {}", 
-                                    screen_text.chars().take(100).collect::<String>(), 
-                                    ocr_confidence);
-                                
-                                if ocr_confidence >= confidence_threshold {
-                                    let context_update = BrainContextUpdate {
-                                        screen_text: screen_text.clone(),
-                                        audio_transcript: String::new(), // TODO: Audio
-                                        patterns: vec![],
-                                        ocr_confidence,
-                                        asr_confidence: 0.0,
-                                        timestamp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as i64,
-                                        metadata: Some(extract_metadata("unknown")),
-                                    };
-                                    
-                                    println!("✅ RUST DEBUG: Sending brain context update to Electron - Text length: {}", screen_text.len());
-                                    
-                                    if tx.send(Ok(context_update)).await.is_err() {
-                                        println!("❌ RUST DEBUG: Failed to send context update to stream");
-                                        break;
-                                    }
-                                } else {
-                                    println!("⚠️ RUST DEBUG: OCR confidence {} below threshold {}", ocr_confidence, confidence_threshold);
+                let frame = match capture_rx.recv().await {
+                    Ok(frame) => frame,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        eprintln!("⚠️ Brain context subscriber lagged, dropped {} frames", skipped);
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+
+                // Sample the shared capture stream instead of capturing independently.
+                if let Some(last) = last_processed_at {
+                    if frame.timestamp - last < interval_ms {
+                        continue;
+                    }
+                }
+                last_processed_at = Some(frame.timestamp);
+                let frame_data = frame.data.clone();
+
+                match send_to_preproc_worker(frame.data, &preproc_endpoint, capture_format).await {
+                    Ok((screen_text, _transcript, ocr_confidence)) => {
+                        println!("🔍 RUST DEBUG: OCR Result - Text: '{}', Confidence: {}",
+                            screen_text.chars().take(100).collect::<String>(),
+                            ocr_confidence);
+
+                        if ocr_confidence >= confidence_threshold {
+                            let transcript = audio_hub.latest_transcript();
+                            let metadata = extract_metadata("unknown");
+                            let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as i64;
+
+                            match search::embed_text(&screen_text, &embedding_endpoint).await {
+                                Ok(embedding) if !embedding.is_empty() => {
+                                    vector_store.insert(screen_text.clone(), embedding, timestamp, Some(metadata.clone()));
                                 }
+                                Ok(_) => {}
+                                Err(e) => eprintln!("❌ Embedding request failed: {}", e),
                             }
-                            Err(e) => {
-                                eprintln!("❌ Preprocessing failed: {}", e);
+
+                            let context_update = BrainContextUpdate {
+                                screen_text: screen_text.clone(),
+                                audio_transcript: transcript.text,
+                                patterns: vec![],
+                                ocr_confidence,
+                                asr_confidence: transcript.confidence,
+                                timestamp,
+                                metadata: Some(metadata),
+                            };
+
+                            let frame_hash = store::content_hash(&frame_data);
+                            if let Err(e) = context_store.put_blob(&frame_hash, &frame_data).await {
+                                eprintln!("❌ Failed to persist captured frame: {}", e);
+                            }
+                            let stored = StoredContextUpdate::from_update(&context_update, Some(frame_hash));
+                            if let Err(e) = context_store.put_update(&stored).await {
+                                eprintln!("❌ Failed to persist brain context update: {}", e);
+                            }
+
+                            // Ignore send errors: no session recorder currently attached.
+                            let _ = context_tx.send(context_update.clone());
+
+                            println!("✅ RUST DEBUG: Sending brain context update to Electron - Text length: {}", screen_text.len());
+
+                            if tx.send(Ok(context_update)).await.is_err() {
+                                println!("❌ RUST DEBUG: Failed to send context update to stream");
+                                break;
                             }
+                        } else {
+                            println!("⚠️ RUST DEBUG: OCR confidence {} below threshold {}", ocr_confidence, confidence_threshold);
                         }
                     }
                     Err(e) => {
-                        eprintln!("❌ Screen capture failed: {}", e);
+                        eprintln!("❌ Preprocessing failed: {}", e);
                     }
                 }
-                
-                tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
             }
         });
 
@@ -170,41 +243,70 @@ impl VelvetCaptureService for VelvetCaptureServiceImpl {
 
         Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
     }
-}
 
-// Simple screen capture using macOS screencapture
-async fn capture_screen_to_png() -> io::Result<Vec<u8>> {
-    let tmp_path = "/tmp/velvet_screencap.png";
-    
-    let status = tokio::process::Command::new("screencapture")
-        .arg("-x") // no UI
-        .arg("-t")
-        .arg("png")
-        .arg(tmp_path)
-        .status()
-        .await?;
-        
-    if !status.success() {
-        return Err(io::Error::new(io::ErrorKind::Other, "screencapture failed"));
+    async fn search_context(
+        &self,
+        request: Request<ContextSearchRequest>,
+    ) -> Result<Response<ContextSearchResponse>, Status> {
+        let req = request.into_inner();
+        let top_k = if req.top_k > 0 { req.top_k as usize } else { 10 };
+
+        let query_embedding = search::embed_text(&req.query, &self.config.preproc.embedding_endpoint)
+            .await
+            .map_err(|e| Status::internal(format!("embedding request failed: {e}")))?;
+
+        let results = self.vector_store.search(&query_embedding, top_k);
+
+        Ok(Response::new(ContextSearchResponse { results }))
+    }
+
+    async fn get_context_history(
+        &self,
+        request: Request<ContextHistoryRequest>,
+    ) -> Result<Response<ContextHistoryResponse>, Status> {
+        let req = request.into_inner();
+        let limit = if req.limit > 0 { req.limit as usize } else { 50 };
+        let before = if req.page_token.is_empty() {
+            None
+        } else {
+            Some(req.page_token.parse::<i64>().map_err(|_| Status::invalid_argument("malformed page_token"))?)
+        };
+
+        let stored = self
+            .context_store
+            .list_updates(before, limit)
+            .await
+            .map_err(|e| Status::internal(format!("failed to read context history: {e}")))?;
+
+        // Only hand back a cursor when the page was full — a short page means
+        // there's nothing older left, so a token here would just cost the
+        // client one extra round-trip that comes back empty.
+        let next_page_token = if stored.len() == limit {
+            stored.last().map(|u| u.timestamp.to_string()).unwrap_or_default()
+        } else {
+            String::new()
+        };
+        let updates = stored.into_iter().map(StoredContextUpdate::into_update).collect();
+
+        Ok(Response::new(ContextHistoryResponse { updates, next_page_token }))
     }
-    
-    let png_bytes = tokio::fs::read(tmp_path).await?;
-    let _ = tokio::fs::remove_file(tmp_path).await;
-    
-    Ok(png_bytes)
 }
 
 // Send to preprocessing worker (simplified)
-async fn send_to_preproc_worker(png_bytes: Vec<u8>) -> Result<(String, String, f32), Box<dyn std::error::Error + Send + Sync + 'static>> {
+async fn send_to_preproc_worker(
+    image_bytes: bytes::Bytes,
+    endpoint: &str,
+    format: config::CaptureFormat,
+) -> Result<(String, String, f32), Box<dyn std::error::Error + Send + Sync + 'static>> {
     let client = reqwest::Client::new();
-    
+
     let form = reqwest::multipart::Form::new()
-        .part("image", reqwest::multipart::Part::bytes(png_bytes)
-            .file_name("frame.png")
-            .mime_str("image/png")?);
-    
+        .part("image", reqwest::multipart::Part::bytes(image_bytes.to_vec())
+            .file_name(format!("frame.{}", format.extension()))
+            .mime_str(format.mime_type())?);
+
     let resp = client
-        .post("http://127.0.0.1:8001/velvet/analyze/")
+        .post(endpoint)
         .multipart(form)
         .send()
         .await?;
@@ -230,19 +332,131 @@ fn extract_metadata(_text: &str) -> velvet_capture::ContextMetadata {
     }
 }
 
+/// Returns the value following `flag` in `args`, e.g. `--replay session.vcap`.
+fn flag_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter().position(|a| a == flag).and_then(|i| args.get(i + 1)).map(String::as_str)
+}
+
+/// Subscribes to `hub`/`audio_hub`/`context_rx` and appends every
+/// frame/chunk/update they publish to a new `SessionRecorder` at `path`.
+async fn spawn_session_recorder(
+    path: std::path::PathBuf,
+    hub: &CaptureHub,
+    audio_hub: &AudioHub,
+    context_rx: broadcast::Receiver<BrainContextUpdate>,
+) -> io::Result<()> {
+    let recorder = std::sync::Arc::new(recording::SessionRecorder::create(&path).await?);
+    println!("⏺️ Recording session to {}", path.display());
+
+    let mut screen_rx = hub.subscribe();
+    let screen_recorder = recorder.clone();
+    tokio::spawn(async move {
+        while let Ok(frame) = screen_rx.recv().await {
+            if let Err(e) = screen_recorder.record_screen_frame(frame).await {
+                eprintln!("❌ Failed to record screen frame: {}", e);
+            }
+        }
+    });
+
+    let mut audio_rx = audio_hub.subscribe();
+    let audio_recorder = recorder.clone();
+    tokio::spawn(async move {
+        while let Ok(chunk) = audio_rx.recv().await {
+            if let Err(e) = audio_recorder.record_audio_chunk(chunk).await {
+                eprintln!("❌ Failed to record audio chunk: {}", e);
+            }
+        }
+    });
+
+    let mut context_rx = context_rx;
+    tokio::spawn(async move {
+        while let Ok(update) = context_rx.recv().await {
+            if let Err(e) = recorder.record_brain_context(update).await {
+                eprintln!("❌ Failed to record brain context update: {}", e);
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Builds the persistence backend selected in `defaults.toml`.
+async fn build_context_store(settings: &config::PersistenceSettings) -> io::Result<Arc<dyn ContextStore>> {
+    match settings.backend {
+        PersistenceBackend::Local => {
+            let store = LocalContextStore::open(std::path::PathBuf::from(&settings.local.dir))?;
+            println!("🧠 Persisting brain context to local store at {}", settings.local.dir);
+            Ok(Arc::new(store))
+        }
+        PersistenceBackend::S3 => {
+            let store = S3ContextStore::connect(&settings.s3).await;
+            println!("🧠 Persisting brain context to S3 bucket {}", settings.s3.bucket);
+            Ok(Arc::new(store))
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let addr = "127.0.0.1:50051".parse()?;
-    let service = VelvetCaptureServiceImpl;
-    
+    let args: Vec<String> = std::env::args().collect();
+
+    if let Some(dir) = flag_value(&args, "--list-sessions") {
+        let sessions = recording::list_sessions(std::path::Path::new(dir)).await?;
+        println!("📼 {} recorded session(s) in {}:", sessions.len(), dir);
+        for session in &sessions {
+            println!(
+                "  {} — {} frames, {:.1}s",
+                session.path.display(),
+                session.frame_count,
+                session.duration_ms as f64 / 1000.0
+            );
+        }
+        return Ok(());
+    }
+
+    let config = CaptureConfig::load();
+
+    let (hub, audio_hub) = if let Some(session_path) = flag_value(&args, "--replay") {
+        let hub = CaptureHub::new();
+        let audio_hub = AudioHub::new();
+        let replayer = recording::SessionReplayer::open(std::path::Path::new(session_path)).await?;
+        tokio::spawn(replayer.replay(hub.clone(), audio_hub.clone()));
+        (hub, audio_hub)
+    } else {
+        let hub = CaptureHub::spawn(config.clone());
+        let audio_hub = AudioHub::spawn(config.audio, config.preproc.asr_endpoint.clone());
+        (hub, audio_hub)
+    };
+
+    let (context_tx, _) = broadcast::channel::<BrainContextUpdate>(8);
+
+    if let Some(session_path) = flag_value(&args, "--record") {
+        spawn_session_recorder(std::path::PathBuf::from(session_path), &hub, &audio_hub, context_tx.subscribe()).await?;
+    }
+
+    let vector_store = VectorStore::default();
+    let context_store = build_context_store(&config.persistence).await?;
+    let shutdown_audio_hub = audio_hub.clone();
+    let service = VelvetCaptureServiceImpl { hub, audio_hub, vector_store, context_store, context_tx, config };
+
     println!("🚀 Velvet Capture Service starting on {}", addr);
     println!("🎥 Screen capture ready");
+    println!("🎤 Audio capture ready");
     println!("🧠 Brain context streaming ready");
-    
-    Server::builder()
+
+    let server = Server::builder()
         .add_service(VelvetCaptureServiceServer::new(service))
-        .serve(addr)
-        .await?;
-        
+        .serve(addr);
+
+    tokio::select! {
+        result = server => result?,
+        _ = tokio::signal::ctrl_c() => {
+            println!("🛑 Shutdown signal received, flushing audio capture...");
+            shutdown_audio_hub.shutdown();
+            tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        }
+    }
+
     Ok(())
 }
\ No newline at end of file
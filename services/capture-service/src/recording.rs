@@ -0,0 +1,215 @@
+use crate::audio::AudioHub;
+use crate::hub::CaptureHub;
+use crate::velvet_capture::{recorded_envelope::Payload, AudioChunk, BrainContextUpdate, RecordedEnvelope, ScreenFrame};
+use prost::Message;
+use std::ffi::OsStr;
+use std::io;
+use std::path::{Path, PathBuf};
+use tokio::fs::{File, OpenOptions};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+use tokio::sync::Mutex;
+
+fn index_path(session_path: &Path) -> PathBuf {
+    session_path.with_extension("idx")
+}
+
+/// The data file, index file, and next write offset for a recording,
+/// guarded together so an append's offset always matches where its bytes
+/// actually land.
+struct RecorderFiles {
+    data: File,
+    index: File,
+    offset: u64,
+}
+
+/// Appends `ScreenFrame`/`AudioChunk`/`BrainContextUpdate` messages, each
+/// wrapped in a `RecordedEnvelope` with its timestamp, to a length-prefixed,
+/// append-only session file, alongside a parallel index of byte offsets so
+/// a recording can be seeked into without a full scan.
+///
+/// Screen, audio, and brain-context updates are each recorded from their own
+/// task against one shared `SessionRecorder`, so the offset, index entry,
+/// and data write for a single envelope are all done under one lock — two
+/// independent locks (or an offset reserved outside the lock) would let
+/// concurrent appends land their bytes out of order with the offsets
+/// recorded for them.
+pub struct SessionRecorder {
+    files: Mutex<RecorderFiles>,
+}
+
+impl SessionRecorder {
+    pub async fn create(session_path: &Path) -> io::Result<Self> {
+        let data = OpenOptions::new().create(true).write(true).truncate(true).open(session_path).await?;
+        let index = OpenOptions::new().create(true).write(true).truncate(true).open(index_path(session_path)).await?;
+
+        Ok(Self {
+            files: Mutex::new(RecorderFiles { data, index, offset: 0 }),
+        })
+    }
+
+    pub async fn record_screen_frame(&self, frame: ScreenFrame) -> io::Result<()> {
+        self.append(frame.timestamp, Payload::ScreenFrame(frame)).await
+    }
+
+    pub async fn record_audio_chunk(&self, chunk: AudioChunk) -> io::Result<()> {
+        self.append(chunk.timestamp, Payload::AudioChunk(chunk)).await
+    }
+
+    pub async fn record_brain_context(&self, update: BrainContextUpdate) -> io::Result<()> {
+        self.append(update.timestamp, Payload::BrainContextUpdate(update)).await
+    }
+
+    async fn append(&self, timestamp: i64, payload: Payload) -> io::Result<()> {
+        let envelope = RecordedEnvelope { timestamp, payload: Some(payload) };
+        let encoded = envelope.encode_to_vec();
+        let len = encoded.len() as u32;
+
+        let mut files = self.files.lock().await;
+        let offset = files.offset;
+
+        files.index.write_all(&offset.to_le_bytes()).await?;
+        files.index.flush().await?;
+
+        files.data.write_all(&len.to_le_bytes()).await?;
+        files.data.write_all(&encoded).await?;
+        files.data.flush().await?;
+
+        files.offset = offset + 4 + encoded.len() as u64;
+        Ok(())
+    }
+}
+
+/// Reads back a session file written by `SessionRecorder` and re-emits its
+/// messages over `hub`/`audio_hub`, sleeping between items to reproduce the
+/// original timing — useful for debugging the OCR/ASR pipeline offline
+/// without live screen or microphone access.
+pub struct SessionReplayer {
+    data: File,
+}
+
+impl SessionReplayer {
+    pub async fn open(session_path: &Path) -> io::Result<Self> {
+        let data = File::open(session_path).await?;
+        Ok(Self { data })
+    }
+
+    async fn next_envelope(&mut self) -> io::Result<Option<RecordedEnvelope>> {
+        let mut len_buf = [0u8; 4];
+        match self.data.read_exact(&mut len_buf).await {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e),
+        }
+
+        let len = u32::from_le_bytes(len_buf) as usize;
+        let mut buf = vec![0u8; len];
+        self.data.read_exact(&mut buf).await?;
+
+        RecordedEnvelope::decode(buf.as_slice())
+            .map(Some)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    pub async fn replay(mut self, hub: CaptureHub, audio_hub: AudioHub) -> io::Result<()> {
+        println!("⏺️ Replaying recorded session...");
+        let mut last_timestamp: Option<i64> = None;
+
+        while let Some(envelope) = self.next_envelope().await? {
+            if let Some(last) = last_timestamp {
+                let gap_ms = (envelope.timestamp - last).max(0) as u64;
+                if gap_ms > 0 {
+                    tokio::time::sleep(tokio::time::Duration::from_millis(gap_ms)).await;
+                }
+            }
+            last_timestamp = Some(envelope.timestamp);
+
+            match envelope.payload {
+                Some(Payload::ScreenFrame(frame)) => hub.publish(frame),
+                Some(Payload::AudioChunk(chunk)) => audio_hub.publish(chunk),
+                Some(Payload::BrainContextUpdate(update)) => {
+                    println!("⏺️ Replayed brain context update: '{}'", update.screen_text.chars().take(80).collect::<String>());
+                }
+                None => {}
+            }
+        }
+
+        println!("⏺️ Session replay finished.");
+        Ok(())
+    }
+}
+
+/// Summary of a recorded session, derived from its index file rather than a
+/// full replay.
+pub struct SessionInfo {
+    pub path: PathBuf,
+    pub frame_count: u64,
+    pub duration_ms: i64,
+}
+
+/// Enumerates recorded sessions in `dir` — any file with a sibling `.idx`
+/// index written by `SessionRecorder` — with their `ScreenFrame` count and
+/// duration (from the earliest and latest envelope timestamps, of any
+/// payload type). The index only gives byte offsets, not payload types, so
+/// getting an accurate frame count means decoding every envelope rather
+/// than just counting index entries (which would count audio chunks and
+/// brain-context updates too).
+pub async fn list_sessions(dir: &Path) -> io::Result<Vec<SessionInfo>> {
+    let mut sessions = Vec::new();
+    let mut entries = tokio::fs::read_dir(dir).await?;
+
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        if !entry.file_type().await?.is_file() || path.extension() == Some(OsStr::new("idx")) {
+            continue;
+        }
+
+        let idx_path = index_path(&path);
+        if !tokio::fs::try_exists(&idx_path).await? {
+            continue;
+        }
+
+        let offsets = tokio::fs::read(&idx_path).await?;
+        if offsets.is_empty() {
+            sessions.push(SessionInfo { path, frame_count: 0, duration_ms: 0 });
+            continue;
+        }
+
+        let mut frame_count = 0u64;
+        let mut min_timestamp = i64::MAX;
+        let mut max_timestamp = i64::MIN;
+
+        for raw_offset in offsets.chunks_exact(8) {
+            let offset = u64::from_le_bytes(raw_offset.try_into().unwrap());
+            let envelope = read_envelope_at(&path, offset).await?;
+
+            if matches!(envelope.payload, Some(Payload::ScreenFrame(_))) {
+                frame_count += 1;
+            }
+            min_timestamp = min_timestamp.min(envelope.timestamp);
+            max_timestamp = max_timestamp.max(envelope.timestamp);
+        }
+
+        sessions.push(SessionInfo {
+            path,
+            frame_count,
+            duration_ms: max_timestamp - min_timestamp,
+        });
+    }
+
+    sessions.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(sessions)
+}
+
+async fn read_envelope_at(session_path: &Path, offset: u64) -> io::Result<RecordedEnvelope> {
+    let mut file = File::open(session_path).await?;
+    file.seek(io::SeekFrom::Start(offset)).await?;
+
+    let mut len_buf = [0u8; 4];
+    file.read_exact(&mut len_buf).await?;
+    let len = u32::from_le_bytes(len_buf) as usize;
+
+    let mut buf = vec![0u8; len];
+    file.read_exact(&mut buf).await?;
+
+    RecordedEnvelope::decode(buf.as_slice()).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
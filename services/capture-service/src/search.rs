@@ -0,0 +1,114 @@
+use crate::velvet_capture::{ContextMetadata, ContextSearchResult};
+use serde_json;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+/// Caps how many embedded snapshots are kept in memory; oldest entries are
+/// evicted first once the store is full.
+const MAX_ENTRIES: usize = 2000;
+
+/// Minimum cosine similarity for a search result to be considered a match
+/// at all, regardless of `top_k`.
+const MIN_SCORE: f32 = 0.2;
+
+/// One embedded snapshot of captured context.
+struct ContextEntry {
+    text: String,
+    embedding: Vec<f32>,
+    timestamp: i64,
+    metadata: Option<ContextMetadata>,
+}
+
+/// In-memory vector store of embedded `screen_text` from every
+/// `BrainContextUpdate`, searchable by natural-language query via
+/// `SearchContext` instead of only being observable on the live stream.
+/// Bounded as a ring buffer so a long-running stream doesn't grow it
+/// without limit.
+#[derive(Clone, Default)]
+pub struct VectorStore {
+    entries: Arc<Mutex<VecDeque<ContextEntry>>>,
+}
+
+impl VectorStore {
+    pub fn insert(&self, text: String, embedding: Vec<f32>, timestamp: i64, metadata: Option<ContextMetadata>) {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= MAX_ENTRIES {
+            entries.pop_front();
+        }
+        entries.push_back(ContextEntry {
+            text,
+            embedding,
+            timestamp,
+            metadata,
+        });
+    }
+
+    /// Returns up to `top_k` entries most similar to `query_embedding` by
+    /// cosine similarity, highest score first, skipping entries below
+    /// `MIN_SCORE` so an unrelated query doesn't return arbitrarily weak
+    /// matches just to fill out `top_k`.
+    pub fn search(&self, query_embedding: &[f32], top_k: usize) -> Vec<ContextSearchResult> {
+        let entries = self.entries.lock().unwrap();
+
+        let mut scored: Vec<(f32, usize)> = entries
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| (cosine_similarity(query_embedding, &entry.embedding), i))
+            .filter(|(score, _)| *score >= MIN_SCORE)
+            .collect();
+
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        scored
+            .into_iter()
+            .take(top_k)
+            .map(|(score, i)| {
+                let entry = &entries[i];
+                ContextSearchResult {
+                    text: entry.text.clone(),
+                    score,
+                    timestamp: entry.timestamp,
+                    metadata: entry.metadata.clone(),
+                }
+            })
+            .collect()
+    }
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|y| y * y).sum::<f32>().sqrt();
+
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+/// Computes a sentence embedding for `text` via the preproc worker's
+/// embedding endpoint.
+pub async fn embed_text(text: &str, endpoint: &str) -> Result<Vec<f32>, Box<dyn std::error::Error + Send + Sync + 'static>> {
+    let client = reqwest::Client::new();
+
+    let resp = client
+        .post(endpoint)
+        .json(&serde_json::json!({ "text": text }))
+        .send()
+        .await?;
+
+    let json: serde_json::Value = resp.json().await?;
+
+    let embedding = json
+        .get("embedding")
+        .and_then(|v| v.as_array())
+        .map(|values| values.iter().filter_map(|v| v.as_f64()).map(|f| f as f32).collect())
+        .unwrap_or_default();
+
+    Ok(embedding)
+}
@@ -0,0 +1,296 @@
+use crate::config::S3StoreSettings;
+use crate::velvet_capture::{BrainContextUpdate, ContextMetadata};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io;
+use std::path::PathBuf;
+
+/// Serializable mirror of `ContextMetadata`, since persisted records are
+/// plain JSON rather than the gRPC wire format.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredMetadata {
+    pub active_app: String,
+    pub active_window_title: String,
+    pub word_count: i32,
+    pub is_communication: bool,
+    pub is_code: bool,
+    pub is_document: bool,
+}
+
+impl From<ContextMetadata> for StoredMetadata {
+    fn from(m: ContextMetadata) -> Self {
+        Self {
+            active_app: m.active_app,
+            active_window_title: m.active_window_title,
+            word_count: m.word_count,
+            is_communication: m.is_communication,
+            is_code: m.is_code,
+            is_document: m.is_document,
+        }
+    }
+}
+
+impl From<StoredMetadata> for ContextMetadata {
+    fn from(m: StoredMetadata) -> Self {
+        Self {
+            active_app: m.active_app,
+            active_window_title: m.active_window_title,
+            word_count: m.word_count,
+            is_communication: m.is_communication,
+            is_code: m.is_code,
+            is_document: m.is_document,
+        }
+    }
+}
+
+/// A `BrainContextUpdate`, as written to the persistence backend. Large
+/// captured frames aren't inlined here — they're written once to blob
+/// storage keyed by content hash and referenced by `frame_blob_hash`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredContextUpdate {
+    pub timestamp: i64,
+    pub screen_text: String,
+    pub audio_transcript: String,
+    pub ocr_confidence: f32,
+    pub asr_confidence: f32,
+    pub metadata: Option<StoredMetadata>,
+    pub frame_blob_hash: Option<String>,
+}
+
+impl StoredContextUpdate {
+    pub fn from_update(update: &BrainContextUpdate, frame_blob_hash: Option<String>) -> Self {
+        Self {
+            timestamp: update.timestamp,
+            screen_text: update.screen_text.clone(),
+            audio_transcript: update.audio_transcript.clone(),
+            ocr_confidence: update.ocr_confidence,
+            asr_confidence: update.asr_confidence,
+            metadata: update.metadata.clone().map(StoredMetadata::from),
+            frame_blob_hash,
+        }
+    }
+
+    pub fn into_update(self) -> BrainContextUpdate {
+        BrainContextUpdate {
+            screen_text: self.screen_text,
+            audio_transcript: self.audio_transcript,
+            patterns: vec![],
+            ocr_confidence: self.ocr_confidence,
+            asr_confidence: self.asr_confidence,
+            timestamp: self.timestamp,
+            metadata: self.metadata.map(ContextMetadata::from),
+        }
+    }
+}
+
+/// Content hash used to key blob storage, so the same frame captured twice
+/// is only written once.
+pub fn content_hash(bytes: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(bytes);
+    format!("{:x}", hasher.finalize())
+}
+
+fn to_io_err<E: std::fmt::Display>(e: E) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, e.to_string())
+}
+
+/// Durable history of brain-context updates, so `stream_brain_context` no
+/// longer discards every update after sending it. Implementations write
+/// each update as a timestamped JSON object and large frames as separate
+/// content-addressed blobs.
+#[tonic::async_trait]
+pub trait ContextStore: Send + Sync {
+    async fn put_update(&self, update: &StoredContextUpdate) -> io::Result<()>;
+    async fn put_blob(&self, hash: &str, bytes: &[u8]) -> io::Result<()>;
+    /// Returns up to `limit` updates older than `before` (or the most
+    /// recent `limit` if `before` is `None`), newest first.
+    async fn list_updates(&self, before: Option<i64>, limit: usize) -> io::Result<Vec<StoredContextUpdate>>;
+}
+
+pub struct LocalContextStore {
+    dir: PathBuf,
+}
+
+impl LocalContextStore {
+    pub fn open(dir: PathBuf) -> io::Result<Self> {
+        std::fs::create_dir_all(dir.join("updates"))?;
+        std::fs::create_dir_all(dir.join("blobs"))?;
+        Ok(Self { dir })
+    }
+
+    fn update_path(&self, timestamp: i64) -> PathBuf {
+        self.dir.join("updates").join(format!("{timestamp}.json"))
+    }
+
+    fn blob_path(&self, hash: &str) -> PathBuf {
+        self.dir.join("blobs").join(hash)
+    }
+}
+
+#[tonic::async_trait]
+impl ContextStore for LocalContextStore {
+    async fn put_update(&self, update: &StoredContextUpdate) -> io::Result<()> {
+        let json = serde_json::to_vec_pretty(update).map_err(to_io_err)?;
+        tokio::fs::write(self.update_path(update.timestamp), json).await
+    }
+
+    async fn put_blob(&self, hash: &str, bytes: &[u8]) -> io::Result<()> {
+        let path = self.blob_path(hash);
+        if tokio::fs::try_exists(&path).await? {
+            return Ok(());
+        }
+        tokio::fs::write(path, bytes).await
+    }
+
+    async fn list_updates(&self, before: Option<i64>, limit: usize) -> io::Result<Vec<StoredContextUpdate>> {
+        let mut entries = tokio::fs::read_dir(self.dir.join("updates")).await?;
+        let mut timestamps = Vec::new();
+        while let Some(entry) = entries.next_entry().await? {
+            if let Some(ts) = entry
+                .file_name()
+                .to_str()
+                .and_then(|name| name.strip_suffix(".json"))
+                .and_then(|stem| stem.parse::<i64>().ok())
+            {
+                timestamps.push(ts);
+            }
+        }
+        timestamps.sort_unstable_by(|a, b| b.cmp(a));
+
+        let mut updates = Vec::new();
+        for ts in timestamps {
+            if before.is_some_and(|before| ts >= before) {
+                continue;
+            }
+            if updates.len() >= limit {
+                break;
+            }
+            let bytes = tokio::fs::read(self.update_path(ts)).await?;
+            updates.push(serde_json::from_slice(&bytes).map_err(to_io_err)?);
+        }
+        Ok(updates)
+    }
+}
+
+/// S3-compatible object storage backend (AWS S3, MinIO, R2, etc).
+pub struct S3ContextStore {
+    client: aws_sdk_s3::Client,
+    bucket: String,
+}
+
+impl S3ContextStore {
+    pub async fn connect(settings: &S3StoreSettings) -> Self {
+        let mut loader = aws_config::defaults(aws_config::BehaviorVersion::latest())
+            .region(aws_sdk_s3::config::Region::new(settings.region.clone()));
+
+        if !settings.endpoint.is_empty() {
+            loader = loader.endpoint_url(settings.endpoint.clone());
+        }
+        if !settings.access_key.is_empty() {
+            loader = loader.credentials_provider(aws_sdk_s3::config::Credentials::new(
+                settings.access_key.clone(),
+                settings.secret_key.clone(),
+                None,
+                None,
+                "velvet-capture-config",
+            ));
+        }
+
+        let client = aws_sdk_s3::Client::new(&loader.load().await);
+        Self { client, bucket: settings.bucket.clone() }
+    }
+
+    fn update_key(timestamp: i64) -> String {
+        format!("updates/{timestamp}.json")
+    }
+
+    fn blob_key(hash: &str) -> String {
+        format!("blobs/{hash}")
+    }
+}
+
+#[tonic::async_trait]
+impl ContextStore for S3ContextStore {
+    async fn put_update(&self, update: &StoredContextUpdate) -> io::Result<()> {
+        let body = serde_json::to_vec(update).map_err(to_io_err)?;
+        self.client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(Self::update_key(update.timestamp))
+            .body(body.into())
+            .send()
+            .await
+            .map_err(to_io_err)?;
+        Ok(())
+    }
+
+    async fn put_blob(&self, hash: &str, bytes: &[u8]) -> io::Result<()> {
+        let key = Self::blob_key(hash);
+        if self.client.head_object().bucket(&self.bucket).key(&key).send().await.is_ok() {
+            return Ok(()); // content-addressed, already stored
+        }
+
+        self.client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .body(bytes.to_vec().into())
+            .send()
+            .await
+            .map_err(to_io_err)?;
+        Ok(())
+    }
+
+    async fn list_updates(&self, before: Option<i64>, limit: usize) -> io::Result<Vec<StoredContextUpdate>> {
+        // list_objects_v2 caps each page at 1000 keys and lists them in
+        // ascending key order, so a bucket holding more updates than that
+        // needs its continuation token followed to reach the newest ones.
+        let mut timestamps: Vec<i64> = Vec::new();
+        let mut continuation_token = None;
+        loop {
+            let mut request = self.client.list_objects_v2().bucket(&self.bucket).prefix("updates/");
+            if let Some(token) = continuation_token {
+                request = request.continuation_token(token);
+            }
+            let listing = request.send().await.map_err(to_io_err)?;
+
+            timestamps.extend(
+                listing
+                    .contents()
+                    .iter()
+                    .filter_map(|o| o.key())
+                    .filter_map(|k| k.strip_prefix("updates/").and_then(|s| s.strip_suffix(".json")))
+                    .filter_map(|s| s.parse::<i64>().ok()),
+            );
+
+            continuation_token = listing.next_continuation_token().map(str::to_string);
+            if continuation_token.is_none() {
+                break;
+            }
+        }
+        timestamps.sort_unstable_by(|a, b| b.cmp(a));
+
+        let mut updates = Vec::new();
+        for ts in timestamps {
+            if before.is_some_and(|before| ts >= before) {
+                continue;
+            }
+            if updates.len() >= limit {
+                break;
+            }
+
+            let object = self
+                .client
+                .get_object()
+                .bucket(&self.bucket)
+                .key(Self::update_key(ts))
+                .send()
+                .await
+                .map_err(to_io_err)?;
+            let bytes = object.body.collect().await.map_err(to_io_err)?.into_bytes();
+            updates.push(serde_json::from_slice(&bytes).map_err(to_io_err)?);
+        }
+        Ok(updates)
+    }
+}